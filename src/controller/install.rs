@@ -0,0 +1,179 @@
+use inner::logger::{Verbosity, Logger, log_fatal, log_verbose};
+use inner::{helpers, json_helper};
+use inner::cache::{self, FetchRequest};
+use std::path::Path;
+use std::{fs, io};
+use git2::Repository;
+use json::{self, JsonValue, object};
+
+pub fn install(verb: &Verbosity, offline: bool) {
+    let logger = Logger::new(verb);
+    let json_path = Path::new("rubigo.json");
+
+    let manifest = match read_json(json_path) {
+        Some(manifest) => manifest,
+        None => {
+            log_fatal("Rubigo project hasn't been initialized", verb);
+            return
+        },
+    };
+
+    let lock_path = Path::new("rubigo.lock");
+    let old_lock = read_json(lock_path).unwrap_or(JsonValue::Null);
+    let new_lock = resolve_lock(&manifest, &old_lock, logger);
+
+    helpers::remove_diff_packages(&old_lock, &new_lock, logger);
+
+    let pool = helpers::new_thread_pool();
+    if !cache::fetch_all(collect_fetch_requests(&new_lock), &pool, offline, logger) {
+        log_fatal("unable to fetch one or more packages", verb);
+        return
+    }
+
+    if let Err(e) = populate_vendor_tree(&new_lock) {
+        log_fatal(e, verb);
+        return
+    }
+
+    let new_lock = with_integrity(&new_lock);
+
+    match json_helper::write_lock(lock_path, &new_lock[json_helper::GIT_KEY], &new_lock[json_helper::LOCAL_KEY]) {
+        Ok(_) => (),
+        Err(e) => {
+            log_fatal(e, verb);
+            return
+        },
+    }
+
+    if !helpers::verify_packages(&new_lock, logger) {
+        log_fatal("package integrity verification failed", verb);
+        return
+    }
+
+    log_verbose("Done", "Packages installed", verb)
+}
+
+fn resolve_lock(manifest: &JsonValue, old_lock: &JsonValue, logger: Logger) -> JsonValue {
+    let mut git_lock = JsonValue::new_array();
+
+    let git = &manifest[json_helper::GIT_KEY];
+    for i in 0..git.len() {
+        let import = match git[i][json_helper::IMPORT_KEY].as_str() {
+            Some(import) => import.to_owned(),
+            None => continue,
+        };
+        let constraint = match git[i]["version"].as_str() {
+            Some(version) => version.to_owned(),
+            None => continue,
+        };
+        let url = match git[i]["url"].as_str() {
+            Some(url) => url.to_owned(),
+            None => continue,
+        };
+
+        let pkg_path = helpers::get_path_from_url(import.as_str());
+        let resolved = match Repository::open(pkg_path.as_path()) {
+            Ok(repo) => match helpers::resolve_version_constraint(&repo, constraint.as_str()) {
+                Some(resolved) => resolved,
+                None => {
+                    logger.error(format!("unable to resolve `{}` for `{}`", constraint, import));
+                    continue
+                },
+            },
+            Err(_) => constraint.clone(),
+        };
+
+        let _ = git_lock.push(object!{
+            json_helper::IMPORT_KEY => import,
+            "url" => url,
+            "version" => resolved,
+        });
+    }
+
+    let local_lock = old_lock[json_helper::LOCAL_KEY].clone();
+    object!{
+        json_helper::GIT_KEY => git_lock,
+        json_helper::LOCAL_KEY => local_lock,
+    }
+}
+
+fn with_integrity(lock: &JsonValue) -> JsonValue {
+    let mut git_lock = JsonValue::new_array();
+
+    let git = &lock[json_helper::GIT_KEY];
+    for i in 0..git.len() {
+        let import = match git[i][json_helper::IMPORT_KEY].as_str() {
+            Some(import) => import.to_owned(),
+            None => continue,
+        };
+        let url = match git[i]["url"].as_str() {
+            Some(url) => url.to_owned(),
+            None => continue,
+        };
+        let version = match git[i]["version"].as_str() {
+            Some(version) => version.to_owned(),
+            None => continue,
+        };
+
+        let pkg_path = helpers::get_path_from_url(import.as_str());
+        let integrity = helpers::compute_integrity(pkg_path.as_path()).ok();
+
+        let _ = git_lock.push(object!{
+            json_helper::IMPORT_KEY => import,
+            "url" => url,
+            "version" => version,
+            json_helper::INTEGRITY_KEY => integrity,
+        });
+    }
+
+    object!{
+        json_helper::GIT_KEY => git_lock,
+        json_helper::LOCAL_KEY => lock[json_helper::LOCAL_KEY].clone(),
+    }
+}
+
+fn populate_vendor_tree(lock: &JsonValue) -> io::Result<()> {
+    let git = &lock[json_helper::GIT_KEY];
+    for i in 0..git.len() {
+        let import = match git[i][json_helper::IMPORT_KEY].as_str() {
+            Some(import) => import,
+            None => continue,
+        };
+        let repo_url = match git[i]["url"].as_str() {
+            Some(url) => url,
+            None => continue,
+        };
+        let revision = match git[i]["version"].as_str() {
+            Some(version) => version,
+            None => continue,
+        };
+        cache::populate_vendor_tree(import, repo_url, revision)?;
+    }
+    Ok(())
+}
+
+fn read_json(path: &Path) -> Option<JsonValue> {
+    match fs::read_to_string(path) {
+        Ok(contents) => json::parse(contents.as_str()).ok(),
+        Err(_) => None,
+    }
+}
+
+fn collect_fetch_requests(lock: &JsonValue) -> Vec<FetchRequest> {
+    let mut requests = vec![];
+
+    let git = &lock[json_helper::GIT_KEY];
+    for i in 0..git.len() {
+        let repo_url = match git[i]["url"].as_str() {
+            Some(url) => url.to_owned(),
+            None => continue,
+        };
+        let revision = match git[i]["version"].as_str() {
+            Some(version) => version.to_owned(),
+            None => continue,
+        };
+        requests.push(FetchRequest { repo_url, revision });
+    }
+
+    requests
+}
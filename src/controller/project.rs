@@ -3,11 +3,13 @@ use std::path::Path;
 use std::fs::{File, create_dir, create_dir_all, remove_dir_all, remove_file};
 use std::env::current_dir;
 use std::fmt::Display;
-use git2::Repository;
+use git2::{Repository, Oid};
 use std::ffi::OsStr;
 use std::io::Write;
 use inner::json_helper;
 use inner::vendor::find_packages;
+use inner::helpers::{get_import_from_path, compute_integrity};
+use json::{JsonValue, object};
 
 pub fn new(name: &str, is_lib: bool, verb: &Verbosity) {
     let path = Path::new(name);
@@ -102,13 +104,102 @@ pub fn init(verb: &Verbosity) {
         }
     } else {
         log_verbose("Synchronize", "vendor directory", verb);
-        let packages = find_packages();
-        // TODO write packages to json file using create_json
+
+        let mut git_packages = JsonValue::new_array();
+        let mut local_packages = JsonValue::new_array();
+        let mut git_lock = JsonValue::new_array();
+        let mut local_lock = JsonValue::new_array();
+
+        for pkg_path in find_packages() {
+            let import = match get_import_from_path(pkg_path.as_path()) {
+                Some(import) => import,
+                None => continue,
+            };
+
+            let integrity = compute_integrity(pkg_path.as_path()).ok();
+
+            match discover_git_package(pkg_path.as_path()) {
+                Some((url, version)) => {
+                    let _ = git_packages.push(object!{
+                        json_helper::IMPORT_KEY => import.clone(),
+                        "url" => url.clone(),
+                        "version" => version.clone(),
+                    });
+                    let _ = git_lock.push(object!{
+                        json_helper::IMPORT_KEY => import,
+                        "url" => url,
+                        "version" => version,
+                        json_helper::INTEGRITY_KEY => integrity,
+                    });
+                },
+                None => {
+                    let _ = local_packages.push(import.clone());
+                    let _ = local_lock.push(object!{
+                        json_helper::IMPORT_KEY => import,
+                        json_helper::INTEGRITY_KEY => integrity,
+                    });
+                },
+            }
+        }
+
+        match json_helper::write(json_path, parent_name, Some((&git_packages, &local_packages))) {
+            Ok(_) => log_verbose("Update file", "rubigo.json", verb),
+            Err(e) => delete_init_project(e, json_path, verb),
+        }
+
+        match json_helper::write_lock(lock_path, &git_lock, &local_lock) {
+            Ok(_) => log_verbose("Create file", "rubigo.lock", verb),
+            Err(e) => delete_init_project(e, json_path, verb),
+        }
     }
 
     log_verbose("Done", "Rubigo project has been initialized", verb)
 }
 
+fn discover_git_package(pkg_path: &Path) -> Option<(String, String)> {
+    let repo = match Repository::open(pkg_path) {
+        Ok(repo) => repo,
+        Err(_) => return None,
+    };
+
+    let url = match repo.find_remote("origin") {
+        Ok(remote) => match remote.url() {
+            Some(url) => url.to_owned(),
+            None => return None,
+        },
+        Err(_) => return None,
+    };
+
+    let head_commit = match repo.head().and_then(|head| head.peel_to_commit()) {
+        Ok(commit) => commit,
+        Err(_) => return None,
+    };
+
+    let version = tag_at_commit(&repo, head_commit.id()).unwrap_or_else(|| head_commit.id().to_string());
+
+    Some((url, version))
+}
+
+fn tag_at_commit(repo: &Repository, oid: Oid) -> Option<String> {
+    let tag_names = match repo.tag_names(None) {
+        Ok(names) => names,
+        Err(_) => return None,
+    };
+
+    for name in tag_names.iter().filter_map(|name| name) {
+        let tagged_commit = match repo.revparse_single(name).and_then(|obj| obj.peel_to_commit()) {
+            Ok(commit) => commit,
+            Err(_) => continue,
+        };
+
+        if tagged_commit.id() == oid {
+            return Some(name.to_owned())
+        }
+    }
+
+    None
+}
+
 fn delete_init_project<T: Display>(err: T, path: &Path, verb: &Verbosity) {
     match remove_file(path) {
         Ok(_) => log_verbose("Delete file", "rubigo.json", verb),
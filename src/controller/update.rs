@@ -0,0 +1,90 @@
+use inner::logger::{Verbosity, Logger, log_fatal, log_verbose};
+use inner::{helpers, json_helper};
+use inner::cache;
+use std::path::Path;
+use std::fs;
+use git2::Repository;
+use json::{self, JsonValue, object};
+
+pub fn update(verb: &Verbosity) {
+    let logger = Logger::new(verb);
+    let json_path = Path::new("rubigo.json");
+
+    let manifest = match read_json(json_path) {
+        Some(manifest) => manifest,
+        None => {
+            log_fatal("Rubigo project hasn't been initialized", verb);
+            return
+        },
+    };
+
+    let lock_path = Path::new("rubigo.lock");
+    let old_lock = read_json(lock_path).unwrap_or(JsonValue::Null);
+    let mut git_lock = JsonValue::new_array();
+
+    let git = &manifest[json_helper::GIT_KEY];
+    for i in 0..git.len() {
+        let import = match git[i][json_helper::IMPORT_KEY].as_str() {
+            Some(import) => import.to_owned(),
+            None => continue,
+        };
+        let constraint = match git[i]["version"].as_str() {
+            Some(version) => version.to_owned(),
+            None => continue,
+        };
+        let url = match git[i]["url"].as_str() {
+            Some(url) => url.to_owned(),
+            None => continue,
+        };
+
+        let pkg_path = helpers::get_path_from_url(import.as_str());
+        let repo = match Repository::open(pkg_path.as_path()) {
+            Ok(repo) => repo,
+            Err(e) => {
+                logger.error(format!("unable to open `{}`: {}", import, e));
+                continue
+            },
+        };
+
+        let resolved = match helpers::resolve_version_constraint(&repo, constraint.as_str()) {
+            Some(resolved) => resolved,
+            None => {
+                logger.error(format!("unable to resolve `{}` for `{}`", constraint, import));
+                continue
+            },
+        };
+
+        if let Err(e) = cache::fetch_package(url.as_str(), resolved.as_str(), false, logger) {
+            logger.error(format!("unable to fetch `{}@{}`: {}", url, resolved, e));
+            continue
+        }
+
+        if let Err(e) = cache::populate_vendor_tree(import.as_str(), url.as_str(), resolved.as_str()) {
+            logger.error(format!("unable to populate `{}`: {}", import, e));
+            continue
+        }
+
+        let integrity = helpers::compute_integrity(pkg_path.as_path()).ok();
+        let _ = git_lock.push(object!{
+            json_helper::IMPORT_KEY => import.clone(),
+            "url" => url,
+            "version" => resolved.clone(),
+            json_helper::INTEGRITY_KEY => integrity,
+        });
+
+        log_verbose("Update package", format!("{} -> {}", import, resolved), verb);
+    }
+
+    let local_lock = old_lock[json_helper::LOCAL_KEY].clone();
+    match json_helper::write_lock(lock_path, &git_lock, &local_lock) {
+        Ok(_) => log_verbose("Done", "Rubigo project has been updated", verb),
+        Err(e) => log_fatal(e, verb),
+    }
+}
+
+fn read_json(path: &Path) -> Option<JsonValue> {
+    match fs::read_to_string(path) {
+        Ok(contents) => json::parse(contents.as_str()).ok(),
+        Err(_) => None,
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use threadpool::ThreadPool;
+use git2::Repository;
+use dirs::cache_dir;
+use sha2::{Sha256, Digest};
+use inner::logger::Logger;
+use inner::helpers::get_path_from_url;
+
+pub struct FetchRequest {
+    pub repo_url: String,
+    pub revision: String,
+}
+
+pub fn cache_root() -> PathBuf {
+    match cache_dir() {
+        Some(dir) => dir.join("rubigo"),
+        None => PathBuf::from(".rubigo-cache"),
+    }
+}
+
+fn cache_key(repo_url: &str, revision: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input(repo_url.as_bytes());
+    hasher.input(b"@");
+    hasher.input(revision.as_bytes());
+    hasher.result().as_slice().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn fetch_package(repo_url: &str, revision: &str, offline: bool, logger: Logger) -> io::Result<PathBuf> {
+    let key = cache_key(repo_url, revision);
+    let dest = cache_root().join(key.as_str());
+
+    if dest.exists() {
+        return Ok(dest)
+    }
+
+    if offline {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("`{}@{}` isn't in the offline cache", repo_url, revision)))
+    }
+
+    let tmp_dest = cache_root().join(format!("{}.tmp", key));
+    let _ = fs::remove_dir_all(tmp_dest.as_path());
+
+    if let Some(parent) = tmp_dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let clone_result = Repository::clone(repo_url, tmp_dest.as_path())
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))
+        .and_then(|repo| checkout_revision(&repo, revision).map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string())));
+
+    if let Err(e) = clone_result {
+        let _ = fs::remove_dir_all(tmp_dest.as_path());
+        return Err(e)
+    }
+
+    fs::rename(tmp_dest.as_path(), dest.as_path())?;
+
+    logger.verbose("Cache package", format!("{}@{}", repo_url, revision).as_str());
+    Ok(dest)
+}
+
+fn checkout_revision(repo: &Repository, revision: &str) -> Result<(), ::git2::Error> {
+    let target = repo.revparse_single(revision)?;
+    repo.checkout_tree(&target, None)?;
+    repo.set_head_detached(target.id())
+}
+
+pub fn fetch_all(requests: Vec<FetchRequest>, pool: &ThreadPool, offline: bool, logger: Logger) -> bool {
+    let mut seen = HashSet::new();
+    let deduped: Vec<FetchRequest> = requests.into_iter()
+        .filter(|request| seen.insert(cache_key(request.repo_url.as_str(), request.revision.as_str())))
+        .collect();
+
+    let (tx, rx) = channel();
+    let total = deduped.len();
+
+    for request in deduped {
+        let tx = tx.clone();
+        pool.execute(move || {
+            let result = fetch_package(request.repo_url.as_str(), request.revision.as_str(), offline, logger);
+            if let Err(ref e) = result {
+                logger.error(format!("unable to fetch `{}@{}`: {}", request.repo_url, request.revision, e));
+            }
+            let _ = tx.send(result.is_ok());
+        });
+    }
+
+    rx.iter().take(total).fold(true, |all_ok, ok| all_ok && ok)
+}
+
+pub fn populate_vendor_tree(import_path: &str, repo_url: &str, revision: &str) -> io::Result<()> {
+    let cached = cache_root().join(cache_key(repo_url, revision));
+    let dest = get_path_from_url(import_path);
+
+    let _ = fs::remove_dir_all(dest.as_path());
+
+    link_or_copy_tree(cached.as_path(), dest.as_path())
+}
+
+fn link_or_copy_tree(src: &Path, dest: &Path) -> io::Result<()> {
+    fs::create_dir_all(dest)?;
+
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dest_path = dest.join(entry.file_name());
+
+        if src_path.file_name() == Some(OsStr::new(".git")) {
+            copy_tree(src_path.as_path(), dest_path.as_path())?;
+        } else if src_path.is_dir() {
+            link_or_copy_tree(src_path.as_path(), dest_path.as_path())?;
+        } else if fs::hard_link(src_path.as_path(), dest_path.as_path()).is_err() {
+            fs::copy(src_path.as_path(), dest_path.as_path())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn copy_tree(src: &Path, dest: &Path) -> io::Result<()> {
+    fs::create_dir_all(dest)?;
+
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dest_path = dest.join(entry.file_name());
+
+        if src_path.is_dir() {
+            copy_tree(src_path.as_path(), dest_path.as_path())?;
+        } else {
+            fs::copy(src_path.as_path(), dest_path.as_path())?;
+        }
+    }
+
+    Ok(())
+}
@@ -11,6 +11,11 @@ use json::JsonValue;
 use inner::logger::Logger;
 use curl::easy::Easy;
 use std::str;
+use std::ffi::OsStr;
+use sha2::{Sha256, Digest};
+use base64;
+use semver::Version;
+use url::Url;
 
 pub fn get_current_dir() -> String {
     match fs::canonicalize(Path::new(Component::CurDir.as_os_str())) {
@@ -99,6 +104,76 @@ pub fn version_prompt(repo: &Repository) -> Option<(String, String)> {
     }
 }
 
+enum VersionConstraint {
+    Exact(Version),
+    Tilde(Version),
+    Caret(Version),
+    Literal(String),
+}
+
+fn parse_version_constraint(constraint: &str) -> VersionConstraint {
+    if constraint.len() == 40 && constraint.chars().all(|c| c.is_digit(16)) {
+        return VersionConstraint::Literal(constraint.to_owned())
+    }
+
+    let (op, rest) = match constraint.chars().next() {
+        Some(op @ '=') | Some(op @ '~') | Some(op @ '^') => (op, &constraint[1..]),
+        _ => return VersionConstraint::Literal(constraint.to_owned()),
+    };
+
+    match Version::parse(rest) {
+        Ok(version) => match op {
+            '=' => VersionConstraint::Exact(version),
+            '~' => VersionConstraint::Tilde(version),
+            _ => VersionConstraint::Caret(version),
+        },
+        Err(_) => VersionConstraint::Literal(constraint.to_owned()),
+    }
+}
+
+fn matches_version_constraint(version: &Version, constraint: &VersionConstraint) -> bool {
+    match *constraint {
+        VersionConstraint::Exact(ref base) => version == base,
+        VersionConstraint::Tilde(ref base) => {
+            version >= base && *version < Version::new(base.major, base.minor + 1, 0)
+        },
+        VersionConstraint::Caret(ref base) => {
+            let upper = if base.major > 0 {
+                Version::new(base.major + 1, 0, 0)
+            } else if base.minor > 0 {
+                Version::new(0, base.minor + 1, 0)
+            } else {
+                Version::new(0, 0, base.patch + 1)
+            };
+            version >= base && *version < upper
+        },
+        VersionConstraint::Literal(_) => false,
+    }
+}
+
+pub fn resolve_version_constraint(repo: &Repository, constraint: &str) -> Option<String> {
+    let parsed = parse_version_constraint(constraint);
+
+    if let VersionConstraint::Literal(ref literal) = parsed {
+        return Some(literal.clone())
+    }
+
+    let tag_names = match repo.tag_names(None) {
+        Ok(names) => names,
+        Err(_) => return None,
+    };
+
+    tag_names.iter()
+        .filter_map(|name| name)
+        .filter_map(|name| match Version::parse(name.trim_start_matches('v')) {
+            Ok(version) => Some((name.to_owned(), version)),
+            Err(_) => None,
+        })
+        .filter(|&(_, ref version)| matches_version_constraint(version, &parsed))
+        .max_by(|a, b| a.1.cmp(&b.1))
+        .map(|(name, _)| name)
+}
+
 pub fn new_thread_pool() -> ThreadPool {
     let threads_num = num_cpus::get();
     ThreadPool::new(if threads_num > 1 {
@@ -108,21 +183,124 @@ pub fn new_thread_pool() -> ThreadPool {
     })
 }
 
+pub struct ImportUrl {
+    host: String,
+    port: Option<u16>,
+    path_segments: Vec<String>,
+}
+
+impl ImportUrl {
+    pub fn parse(pkg_import: &str) -> Option<ImportUrl> {
+        ImportUrl::parse_scp_style(pkg_import).or_else(|| ImportUrl::parse_url(pkg_import))
+    }
+
+    fn parse_url(pkg_import: &str) -> Option<ImportUrl> {
+        let url = if pkg_import.contains("://") {
+            match Url::parse(pkg_import) {
+                Ok(url) => url,
+                Err(_) => return None,
+            }
+        } else {
+            match Url::parse(format!("https://{}", pkg_import).as_str()) {
+                Ok(url) => url,
+                Err(_) => return None,
+            }
+        };
+
+        let host = match url.host_str() {
+            Some(host) => host.to_lowercase(),
+            None => return None,
+        };
+
+        let path_segments = match url.path_segments() {
+            Some(segments) => segments.filter(|segment| !segment.is_empty()).map(|segment| segment.to_owned()).collect(),
+            None => vec![],
+        };
+
+        Some(ImportUrl { host, port: url.port(), path_segments })
+    }
+
+    fn parse_scp_style(pkg_import: &str) -> Option<ImportUrl> {
+        if pkg_import.contains("://") {
+            return None
+        }
+
+        let at_pos = pkg_import.find('@')?;
+        let rest = &pkg_import[at_pos + 1..];
+        let colon_pos = rest.find(':')?;
+        let (host, path) = rest.split_at(colon_pos);
+        let path = path[1..].trim_end_matches(".git");
+
+        if host.is_empty() || path.is_empty() {
+            return None
+        }
+
+        let path_segments = path.split('/').filter(|segment| !segment.is_empty()).map(|segment| segment.to_owned()).collect();
+
+        Some(ImportUrl { host: host.to_lowercase(), port: None, path_segments })
+    }
+
+    fn host_with_port(&self) -> String {
+        match self.port {
+            Some(port) => format!("{}:{}", self.host, port),
+            None => self.host.clone(),
+        }
+    }
+
+    pub fn import_path(&self) -> String {
+        let mut parts = vec![self.host_with_port()];
+        parts.extend(self.path_segments.iter().cloned());
+        parts.join("/")
+    }
+
+    pub fn vendor_path(&self) -> PathBuf {
+        let mut pkg_path_buf = PathBuf::from(VENDOR_DIR);
+        pkg_path_buf.push(self.host_with_port().as_str());
+        for segment in &self.path_segments {
+            pkg_path_buf.push(segment.as_str())
+        }
+        pkg_path_buf
+    }
+}
+
 pub fn strip_url_scheme(pkg_import: &str) -> String {
-    let re = match Regex::new(r"https?://") {
-        Ok(re) => re,
-        _ => return pkg_import.to_owned(),
-    };
-    re.replace_all(pkg_import, "").into_owned()
+    match ImportUrl::parse(pkg_import) {
+        Some(import_url) => import_url.import_path(),
+        None => pkg_import.to_owned(),
+    }
 }
 
 pub fn get_path_from_url(pkg_import: &str) -> PathBuf {
-    let mut pkg_path_buf = PathBuf::from(VENDOR_DIR);
-    let path_segments = pkg_import.split("/");
-    for segment in path_segments {
-        pkg_path_buf.push(segment)
+    match ImportUrl::parse(pkg_import) {
+        Some(import_url) => import_url.vendor_path(),
+        None => {
+            let mut pkg_path_buf = PathBuf::from(VENDOR_DIR);
+            for segment in pkg_import.split('/') {
+                pkg_path_buf.push(segment)
+            }
+            pkg_path_buf
+        },
+    }
+}
+
+pub fn get_import_from_path(pkg_path: &Path) -> Option<String> {
+    let relative = match pkg_path.strip_prefix(VENDOR_DIR) {
+        Ok(relative) => relative,
+        Err(_) => return None,
+    };
+
+    let segments: Vec<&str> = relative.components()
+        .filter_map(|component| match component {
+            Component::Normal(segment) => segment.to_str(),
+            _ => None,
+        })
+        .collect();
+
+    if segments.is_empty() {
+        None
+    } else {
+        Some(segments.join("/"))
     }
-    pkg_path_buf
 }
 
 pub fn remove_diff_packages(old_lock: &JsonValue, new_lock: &JsonValue, logger: Logger) {
@@ -155,12 +333,12 @@ pub fn remove_diff_packages(old_lock: &JsonValue, new_lock: &JsonValue, logger:
     if !old_local.is_null() {
         let new_local = &new_lock[json_helper::LOCAL_KEY];
         'outer2: for i in 0..old_local.len() {
-            let old_pkg_name = match old_local[i].as_str() {
+            let old_pkg_name = match old_local[i][json_helper::IMPORT_KEY].as_str() {
                 Some(name) => name,
                 None => continue 'outer2,
             };
             'inner2: for j in 0..new_local.len() {
-                let new_pkg_name = match new_local[j].as_str() {
+                let new_pkg_name = match new_local[j][json_helper::IMPORT_KEY].as_str() {
                     Some(name) => name,
                     None => continue 'inner2,
                 };
@@ -195,60 +373,146 @@ pub fn remove_package(dir_path: &str, logger: Logger) -> bool {
     true
 }
 
-pub fn modify_golang_org(repo_url: &str) -> (String, Option<String>) {
-    if repo_url.starts_with("golang.org/x") {
-        let mut buf = String::new();
-        {
-            let mut handle = Easy::new();
-            match handle.url(repo_url) {
-                Ok(_) => (),
-                _ => return (format!("http://{}", repo_url), None),
-            };
-            let mut transfer = handle.transfer();
-            match transfer.write_function(|data| {
-                match str::from_utf8(data) {
-                    Ok(s) => {
-                        buf.push_str(s);
-                        Ok(data.len())
-                    },
-                    _ => Ok(0),
-                }
-            }) {
-                Ok(_) => (),
-                _ => return (format!("http://{}", repo_url), None),
-            };
-            match transfer.perform() {
-                Ok(_) => (),
-                _ => return (format!("http://{}", repo_url), None),
-            };
+pub fn compute_integrity(pkg_path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    hash_dir(pkg_path, &mut hasher)?;
+    Ok(format!("sha256-{}", base64::encode(hasher.result().as_slice())))
+}
+
+fn hash_dir(dir: &Path, hasher: &mut Sha256) -> io::Result<()> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        if path.file_name() == Some(OsStr::new(".git")) {
+            continue
         }
-        let re = match Regex::new(r#".*go-import.* git ([^'"]*)"?'?>"#) {
-            Ok(r) => r,
-            _ => return (format!("http://{}", repo_url), None),
+
+        if path.is_dir() {
+            hash_dir(path.as_path(), hasher)?;
+        } else {
+            hasher.input(path.to_string_lossy().as_bytes());
+            hasher.input(fs::read(path.as_path())?.as_slice());
+        }
+    }
+
+    Ok(())
+}
+
+pub fn verify_package_integrity(pkg_name: &str, expected_integrity: &str, logger: Logger) -> bool {
+    let pkg_path = get_path_from_url(pkg_name);
+    let actual_integrity = match compute_integrity(pkg_path.as_path()) {
+        Ok(integrity) => integrity,
+        Err(e) => {
+            logger.error(format!("unable to compute integrity for `{}`: {}", pkg_name, e));
+            return false
+        },
+    };
+
+    if actual_integrity != expected_integrity {
+        logger.error(format!("integrity mismatch for `{}`: expected `{}`, got `{}`", pkg_name, expected_integrity, actual_integrity));
+        return false
+    }
+
+    true
+}
+
+pub fn verify_packages(lock: &JsonValue, logger: Logger) -> bool {
+    let git = &lock[json_helper::GIT_KEY];
+    for i in 0..git.len() {
+        let pkg_name = match git[i][json_helper::IMPORT_KEY].as_str() {
+            Some(name) => name,
+            None => continue,
         };
-        let cap = match re.captures(buf.as_str()) {
-            Some(c) => c,
-            None => return (format!("http://{}", repo_url), None),
+        let integrity = match git[i][json_helper::INTEGRITY_KEY].as_str() {
+            Some(integrity) => integrity,
+            None => continue,
         };
-        return match cap.get(1) {
-            Some(s) => {
-                let url = s.as_str();
-                let re = match Regex::new(r#"[^/]*//[^/]*/(.*)"#) {
-                    Ok(r) => r,
-                    _ => return (url.to_owned(), None),
-                };
-                let cap = match re.captures(url) {
-                    Some(c) => c,
-                    None => return (url.to_owned(), None),
-                };
-                match cap.get(1) {
-                    Some(p) => return (url.to_owned(), Some(format!("golang.org/x/{}", p.as_str()))),
-                    None => (),
-                };
-                (url.to_owned(), None)
-            },
-            _ => (format!("http://{}", repo_url), None),
+        if !verify_package_integrity(pkg_name, integrity, logger) {
+            return false
+        }
+    }
+
+    let local = &lock[json_helper::LOCAL_KEY];
+    for i in 0..local.len() {
+        let pkg_name = match local[i][json_helper::IMPORT_KEY].as_str() {
+            Some(name) => name,
+            None => continue,
+        };
+        let integrity = match local[i][json_helper::INTEGRITY_KEY].as_str() {
+            Some(integrity) => integrity,
+            None => continue,
+        };
+        if !verify_package_integrity(pkg_name, integrity, logger) {
+            return false
         }
     }
-    (format!("http://{}", repo_url), None)
+
+    true
+}
+
+struct GoImportMeta {
+    prefix: String,
+    vcs: String,
+    repo_root: String,
+}
+
+fn fetch_go_get_body(import_path: &str) -> Option<String> {
+    let mut buf = String::new();
+    {
+        let mut handle = Easy::new();
+        handle.url(format!("https://{}?go-get=1", import_path).as_str()).ok()?;
+        let mut transfer = handle.transfer();
+        transfer.write_function(|data| {
+            match str::from_utf8(data) {
+                Ok(s) => {
+                    buf.push_str(s);
+                    Ok(data.len())
+                },
+                _ => Ok(0),
+            }
+        }).ok()?;
+        transfer.perform().ok()?;
+    }
+    Some(buf)
+}
+
+fn parse_go_import_meta(body: &str) -> Vec<GoImportMeta> {
+    let re = match Regex::new(r#"<meta\s+name="go-import"\s+content="([^"]+)"\s*/?>"#) {
+        Ok(re) => re,
+        Err(_) => return vec![],
+    };
+
+    re.captures_iter(body)
+        .filter_map(|cap| cap.get(1).map(|content| content.as_str().split_whitespace().collect::<Vec<&str>>()))
+        .filter_map(|fields| match fields.as_slice() {
+            [prefix, vcs, repo_root] => Some(GoImportMeta {
+                prefix: prefix.to_string(),
+                vcs: vcs.to_string(),
+                repo_root: repo_root.to_string(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn modify_golang_org(repo_url: &str) -> (String, Option<String>) {
+    let fallback = (format!("http://{}", repo_url), None);
+
+    let body = match fetch_go_get_body(repo_url) {
+        Some(body) => body,
+        None => return fallback,
+    };
+
+    let best_match = parse_go_import_meta(body.as_str()).into_iter()
+        .filter(|meta| repo_url == meta.prefix.as_str() || repo_url.starts_with(format!("{}/", meta.prefix).as_str()))
+        .max_by_key(|meta| meta.prefix.len());
+
+    match best_match {
+        Some(ref meta) if meta.vcs == "git" => (meta.repo_root.clone(), Some(meta.prefix.clone())),
+        _ => fallback,
+    }
 }
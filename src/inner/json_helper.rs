@@ -0,0 +1,35 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use json::{self, JsonValue, object};
+
+pub const GIT_KEY: &'static str = "git";
+pub const LOCAL_KEY: &'static str = "local";
+pub const IMPORT_KEY: &'static str = "import";
+pub const INTEGRITY_KEY: &'static str = "integrity";
+
+pub fn write<P: AsRef<Path>>(path: P, name: &str, packages: Option<(&JsonValue, &JsonValue)>) -> io::Result<()> {
+    let (git, local) = match packages {
+        Some((git, local)) => (git.clone(), local.clone()),
+        None => (JsonValue::new_array(), JsonValue::new_array()),
+    };
+
+    let contents = object!{
+        "name" => name,
+        GIT_KEY => git,
+        LOCAL_KEY => local,
+    };
+
+    let mut file = File::create(path)?;
+    file.write_all(json::stringify_pretty(contents, 4).as_bytes())
+}
+
+pub fn write_lock<P: AsRef<Path>>(path: P, git_lock: &JsonValue, local_lock: &JsonValue) -> io::Result<()> {
+    let contents = object!{
+        GIT_KEY => git_lock.clone(),
+        LOCAL_KEY => local_lock.clone(),
+    };
+
+    let mut file = File::create(path)?;
+    file.write_all(json::stringify_pretty(contents, 4).as_bytes())
+}